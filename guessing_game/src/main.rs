@@ -1,68 +1,76 @@
-use geo::{Polygon, LineString, Coord};
+use geo::{Polygon, LineString, Coord, GeoNum};
 use geo::CoordsIter;
-use ordered_float::OrderedFloat;
-use std::collections::HashMap;
-
-type Point = (OrderedFloat<f64>, OrderedFloat<f64>);
-type Edge = (Point, Point);
+use num_traits::Float;
+use smallvec::SmallVec;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+/// Numeric bound for the decomposition pipeline: `geo`'s `GeoNum` for
+/// robust predicates, plus `Float` for the epsilon/abs/zero arithmetic.
+trait Coordinate: GeoNum + Float {}
+impl<T: GeoNum + Float> Coordinate for T {}
+
+/// Everything that can go wrong turning a possibly-malformed polygon into a
+/// convex decomposition, instead of aborting the process.
+#[derive(Debug, Clone, PartialEq)]
+enum DecompositionError {
+    TooFewVertices { got: usize },
+    NoEarFound,
+    SharedEdgeNotFound,
+    NoVisibleBridge,
+    DegenerateFace,
+    NonFiniteCoordinate,
+}
 
-fn hertel_mehlhorn(polygon: &Polygon<f64>) -> Vec<Polygon<f64>> {
-    // Step 1: Triangulate the polygon
-    let mut triangles = triangulate(polygon);
-    println!("Step 1: Triangulated polygons:");
-    for (i, triangle) in triangles.iter().enumerate() {
-        println!("  Triangle {}: {:?}", i + 1, triangle);
+impl fmt::Display for DecompositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecompositionError::TooFewVertices { got } => {
+                write!(f, "polygon must have at least 3 vertices for triangulation, got {got}")
+            }
+            DecompositionError::NoEarFound => {
+                write!(f, "no ear found; the polygon might be invalid or self-intersecting")
+            }
+            DecompositionError::SharedEdgeNotFound => {
+                write!(f, "shared edge not found while merging two faces")
+            }
+            DecompositionError::NoVisibleBridge => {
+                write!(f, "no exterior edge visible from a hole vertex")
+            }
+            DecompositionError::DegenerateFace => {
+                write!(f, "face has zero area and no well-defined supporting plane")
+            }
+            DecompositionError::NonFiniteCoordinate => {
+                write!(f, "polygon contains a NaN or infinite coordinate")
+            }
+        }
     }
+}
 
-    // Step 2: Find all shared edges
-    let shared_edges = find_shared_edges(&triangles);
-    println!("Step 2: Shared edges with triangles:");
-    for (i, (edge, (t1, t2))) in shared_edges.iter().enumerate() {
-        println!("  Edge {}: {:?} shared by triangles {} and {}", i + 1, edge, t1, t2);
-    }
+impl Error for DecompositionError {}
 
-    // Step 3: Merge triangles into convex polygons
-    let mut merged_polygons = triangles.clone();
-    let mut to_remove = vec![false; merged_polygons.len()]; // Flags for triangles that get merged.
+/// A triangle as an index triple into a shared vertex buffer.
+type Triangle = [usize; 3];
 
-    for (edge, (t1, t2)) in shared_edges {
-        // Skip if either triangle has already been merged
-        if to_remove[t1] || to_remove[t2] {
-            continue;
-        }
+/// An undirected edge, keyed by its two vertex indices in sorted order so
+/// that both winding directions of the same edge hash to one entry.
+type EdgeKey = (usize, usize);
 
-        // Merge the two triangles into a single polygon
-        let merged_polygon = merge_polygons(&merged_polygons[t1], &merged_polygons[t2], &edge);
-
-        // Check if the merged polygon is convex
-        if is_polygon_convex(&merged_polygon) {
-            println!("Merging triangles {} and {} into a convex polygon.", t1 + 1, t2 + 1);
-        
-            // Add the new merged polygon
-            merged_polygons.push(merged_polygon);
-        
-            // Mark t1 and t2 as merged
-            to_remove[t1] = true;
-            to_remove[t2] = true;
-        
-            // Add a new entry for the new polygon
-            to_remove.push(false);
-        } else {
-            println!("Triangles {} and {} cannot be merged into a convex polygon.", t1 + 1, t2 + 1);
-        }
-        
-    }
+fn edge_key(a: usize, b: usize) -> EdgeKey {
+    if a < b { (a, b) } else { (b, a) }
+}
 
-    println!("Length of merged_polygons: {}", merged_polygons.len());
-    println!("Length of to_remove: {}", to_remove.len());
-    
+fn hertel_mehlhorn<T: Coordinate>(polygon: &Polygon<T>) -> Result<Vec<Polygon<T>>, DecompositionError> {
+    let decomposition = decompose_faces(polygon)?;
 
-    // Collect the remaining unmerged polygons
-    let final_polygons: Vec<Polygon<f64>> = merged_polygons
+    let final_polygons: Vec<Polygon<T>> = decomposition
+        .faces
         .into_iter()
         .enumerate()
-        .filter(|(i, _)| !to_remove[*i])
-        .map(|(_, poly)| poly)
+        .filter(|(i, _)| decomposition.alive[*i])
+        .map(|(_, face)| face_to_polygon(&decomposition.vertices, &face))
         .collect();
 
     println!("Step 3: Final convex polygons:");
@@ -70,172 +78,490 @@ fn hertel_mehlhorn(polygon: &Polygon<f64>) -> Vec<Polygon<f64>> {
         println!("  Convex Polygon {}: {:?}", i + 1, poly);
     }
 
-    final_polygons
+    Ok(final_polygons)
 }
 
-/// Modify `find_shared_edges` to also return the indices of triangles sharing the edges.
-fn find_shared_edges(triangles: &[Polygon<f64>]) -> Vec<(Edge, (usize, usize))> {
-    let mut edge_map: HashMap<Edge, Vec<usize>> = HashMap::new();
+/// Intermediate state of a Hertel-Mehlhorn run: the shared vertex buffer,
+/// every face created so far, which of those faces survived, and the
+/// current edge/face adjacency.
+struct Decomposition<T: Coordinate> {
+    vertices: Vec<Coord<T>>,
+    faces: Vec<Vec<usize>>,
+    alive: Vec<bool>,
+    edge_faces: HashMap<EdgeKey, SmallVec<[usize; 2]>>,
+}
 
+fn decompose_faces<T: Coordinate>(polygon: &Polygon<T>) -> Result<Decomposition<T>, DecompositionError> {
+    // Step 1: Triangulate the polygon into a shared vertex buffer + index triples
+    let (vertices, triangles) = triangulate(polygon)?;
+    println!("Step 1: Triangulated into {} triangles over {} vertices:", triangles.len(), vertices.len());
     for (i, triangle) in triangles.iter().enumerate() {
-        let coords = triangle.exterior().coords_iter().collect::<Vec<_>>();
-        for j in 0..3 {
-            let edge = (
-                (OrderedFloat(coords[j].x), OrderedFloat(coords[j].y)),
-                (OrderedFloat(coords[(j + 1) % 3].x), OrderedFloat(coords[(j + 1) % 3].y)),
-            );
-            let normalized_edge = if edge.0 < edge.1 { edge } else { (edge.1, edge.0) };
-            edge_map.entry(normalized_edge).or_insert_with(Vec::new).push(i);
-        }
+        println!("  Triangle {}: {:?}", i + 1, triangle);
     }
 
-    edge_map
-        .into_iter()
-        .filter_map(|(edge, indices)| {
-            if indices.len() == 2 {
-                Some((edge, (indices[0], indices[1])))
-            } else {
-                None
+    // Step 2: Find all shared edges, keyed by sorted vertex-index pairs.
+    // Edges with two incident triangles are internal diagonals; these are the
+    // only edges Hertel-Mehlhorn ever considers removing.
+    let mut edge_faces = find_shared_edges(&triangles);
+    let diagonals: Vec<EdgeKey> = edge_faces
+        .iter()
+        .filter(|(_, incident)| incident.len() == 2)
+        .map(|(&edge, _)| edge)
+        .collect();
+    println!("Step 2: {} internal diagonals found.", diagonals.len());
+
+    // Step 3: Repeatedly remove diagonals whose removal keeps both endpoints
+    // convex, letting convex pieces absorb as many neighbors as they can
+    // instead of only ever merging in pairs.
+    let mut faces: Vec<Vec<usize>> = triangles.iter().map(|t| t.to_vec()).collect();
+    let mut alive = vec![true; faces.len()];
+    let mut removed = vec![false; diagonals.len()];
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for (d, &diagonal) in diagonals.iter().enumerate() {
+            if removed[d] {
+                continue;
             }
-        })
-        .collect()
+
+            let incident = &edge_faces[&diagonal];
+            if incident.len() != 2 {
+                continue; // Already fused into the boundary of a merged face.
+            }
+            let (f1, f2) = (incident[0], incident[1]);
+
+            let merged_face = merge_faces(&faces[f1], &faces[f2], diagonal)?;
+
+            // Only the two diagonal endpoints can change from convex to
+            // concave by this merge; every other vertex's incident angle is
+            // untouched, so that's all that needs rechecking.
+            if is_vertex_convex_in_face(&vertices, &merged_face, diagonal.0)
+                && is_vertex_convex_in_face(&vertices, &merged_face, diagonal.1)
+            {
+                println!("Removing diagonal {:?}: fusing faces {} and {}.", diagonal, f1, f2);
+
+                let new_face = faces.len();
+                update_edge_faces(&mut edge_faces, &faces[f1], f1, new_face);
+                update_edge_faces(&mut edge_faces, &faces[f2], f2, new_face);
+                edge_faces.remove(&diagonal);
+
+                faces.push(merged_face);
+                alive.push(true);
+                alive[f1] = false;
+                alive[f2] = false;
+                removed[d] = true;
+                changed = true;
+            }
+        }
+    }
+
+    Ok(Decomposition { vertices, faces, alive, edge_faces })
 }
 
-/// Merge two polygons along a shared edge, preserving anticlockwise order
-/// and removing consecutive duplicate points.
-fn merge_polygons(p1: &Polygon<f64>, p2: &Polygon<f64>, shared_edge: &Edge) -> Polygon<f64> {
-    // Extract exterior coordinates
-    let coords1 = p1.exterior().coords_iter().collect::<Vec<_>>();
-    let coords2 = p2.exterior().coords_iter().collect::<Vec<_>>();
+/// A navmesh: the convex cells, each cell's centroid (used as its A*/Dijkstra
+/// node position, at the same index), and the portals connecting the cells.
+struct NavMesh<T: Coordinate> {
+    polygons: Vec<Polygon<T>>,
+    centroids: Vec<Coord<T>>,
+    portals: Vec<Portal<T>>,
+}
 
-    // Convert shared edge into coordinates
-    let shared_start = Coord {
-        x: shared_edge.0 .0.into(),
-        y: shared_edge.0 .1.into(),
-    };
-    let shared_end = Coord {
-        x: shared_edge.1 .0.into(),
-        y: shared_edge.1 .1.into(),
-    };
+/// A shared edge between two adjacent convex cells, usable as a funnel
+/// algorithm portal when string-pulling a path across the navmesh.
+struct Portal<T: Coordinate> {
+    cells: (usize, usize),
+    endpoints: (Coord<T>, Coord<T>),
+}
+
+/// Decompose a polygon into convex cells and the portal graph connecting
+/// them, turning the decomposition directly into something an A*/Dijkstra
+/// search can run over.
+fn decompose_to_navmesh<T: Coordinate>(polygon: &Polygon<T>) -> Result<NavMesh<T>, DecompositionError> {
+    let decomposition = decompose_faces(polygon)?;
+
+    // Renumber surviving faces into a dense 0..polygons.len() id space.
+    let mut cell_id = vec![usize::MAX; decomposition.faces.len()];
+    let mut polygons = Vec::new();
+    let mut centroids = Vec::new();
+    for (i, face) in decomposition.faces.iter().enumerate() {
+        if decomposition.alive[i] {
+            cell_id[i] = polygons.len();
+            polygons.push(face_to_polygon(&decomposition.vertices, face));
+            centroids.push(face_centroid(&decomposition.vertices, face));
+        }
+    }
 
-    // Find the shared edge indices in each polygon
-    let shared_idx1 = find_shared_edge(&coords1, shared_start, shared_end);
-    let shared_idx2 = find_shared_edge(&coords2, shared_start, shared_end);
+    // Any edge still shared by two surviving cells after the merge loop is a
+    // portal: the merge loop retries every remaining diagonal until a full
+    // pass removes none, so removing this edge would have left one of its
+    // endpoints concave.
+    let mut portals = Vec::new();
+    for (&edge, incident) in &decomposition.edge_faces {
+        if incident.len() != 2 {
+            continue;
+        }
+        let (a, b) = (incident[0], incident[1]);
+        if a == b {
+            continue;
+        }
 
-    // Reorder polygons to start after the shared edge
-    let mut merged_coords = reorder_polygon(&coords1, shared_idx1);
-    merged_coords.extend(
-        reorder_polygon(&coords2, shared_idx2)
-            .into_iter()
-            .filter(|&coord| coord != shared_start && coord != shared_end),
-    );
+        portals.push(Portal {
+            cells: (cell_id[a].min(cell_id[b]), cell_id[a].max(cell_id[b])),
+            endpoints: (decomposition.vertices[edge.0], decomposition.vertices[edge.1]),
+        });
+    }
+
+    Ok(NavMesh { polygons, centroids, portals })
+}
+
+/// The centroid of a face's vertex-index ring, averaged over its vertices.
+fn face_centroid<T: Coordinate>(vertices: &[Coord<T>], face: &[usize]) -> Coord<T> {
+    let sum = face
+        .iter()
+        .fold(Coord { x: T::zero(), y: T::zero() }, |acc, &i| Coord {
+            x: acc.x + vertices[i].x,
+            y: acc.y + vertices[i].y,
+        });
+    let len = T::from(face.len()).expect("face length fits in T");
+    Coord { x: sum.x / len, y: sum.y / len }
+}
+
+/// The vertex-index edges that make up a face's ring, in winding order.
+fn face_edges(face: &[usize]) -> impl Iterator<Item = EdgeKey> + '_ {
+    let len = face.len();
+    (0..len).map(move |i| edge_key(face[i], face[(i + 1) % len]))
+}
+
+/// After a face is retired into a merged one, repoint every edge that used
+/// to reference it at the new face's index instead.
+fn update_edge_faces(
+    edge_faces: &mut HashMap<EdgeKey, SmallVec<[usize; 2]>>,
+    face: &[usize],
+    old_id: usize,
+    new_id: usize,
+) {
+    for edge in face_edges(face) {
+        if let Some(incident) = edge_faces.get_mut(&edge) {
+            for id in incident.iter_mut() {
+                if *id == old_id {
+                    *id = new_id;
+                }
+            }
+        }
+    }
+}
+
+/// Check convexity at a single vertex of a face, using only its predecessor
+/// and successor in the ring.
+fn is_vertex_convex_in_face<T: Coordinate>(vertices: &[Coord<T>], face: &[usize], vertex: usize) -> bool {
+    let len = face.len();
+    let i = face
+        .iter()
+        .position(|&v| v == vertex)
+        .expect("vertex not found in face");
+    let prev = vertices[face[(i + len - 1) % len]];
+    let curr = vertices[face[i]];
+    let next = vertices[face[(i + 1) % len]];
+    is_convex(prev, curr, next)
+}
 
-    // Close the polygon by appending the starting point
-    merged_coords.push(merged_coords[0]);
+/// Build the edge adjacency graph: for every edge, which triangles touch
+/// it. Two incident triangles means an internal diagonal; one means a
+/// polygon boundary edge.
+fn find_shared_edges(triangles: &[Triangle]) -> HashMap<EdgeKey, SmallVec<[usize; 2]>> {
+    let mut edge_map: HashMap<EdgeKey, SmallVec<[usize; 2]>> = HashMap::new();
 
-    // Remove consecutive duplicates
-    merged_coords.dedup_by(|a, b| a == b);
+    for (i, triangle) in triangles.iter().enumerate() {
+        for j in 0..3 {
+            let edge = edge_key(triangle[j], triangle[(j + 1) % 3]);
+            edge_map.entry(edge).or_default().push(i);
+        }
+    }
 
-    Polygon::new(LineString::from(merged_coords), vec![])
+    edge_map
 }
 
+/// Merge two faces (vertex-index rings) along a shared edge, preserving
+/// anticlockwise order. The shared edge's two endpoints are kept once each;
+/// only the second face's copies of them are dropped.
+fn merge_faces(face1: &[usize], face2: &[usize], shared_edge: EdgeKey) -> Result<Vec<usize>, DecompositionError> {
+    // Find the shared edge indices in each face
+    let shared_idx1 = find_shared_edge_in_face(face1, shared_edge)?;
+    let shared_idx2 = find_shared_edge_in_face(face2, shared_edge)?;
+
+    // Reorder faces to start after the shared edge
+    let mut merged = reorder_face(face1, shared_idx1);
+    merged.extend(
+        reorder_face(face2, shared_idx2)
+            .into_iter()
+            .filter(|&v| v != shared_edge.0 && v != shared_edge.1),
+    );
+
+    Ok(merged)
+}
 
-/// Find the starting index of the shared edge in a polygon.
-fn find_shared_edge(
-    coords: &[Coord<f64>],
-    shared_start: Coord<f64>,
-    shared_end: Coord<f64>,
-) -> usize {
-    coords
-        .windows(2)
-        .position(|edge| (edge[0] == shared_start && edge[1] == shared_end)
-            || (edge[0] == shared_end && edge[1] == shared_start))
-        .expect("Shared edge not found in polygon")
+/// Find the starting index of the shared edge within a face's vertex ring.
+fn find_shared_edge_in_face(face: &[usize], shared_edge: EdgeKey) -> Result<usize, DecompositionError> {
+    let len = face.len();
+    (0..len)
+        .position(|i| edge_key(face[i], face[(i + 1) % len]) == shared_edge)
+        .ok_or(DecompositionError::SharedEdgeNotFound)
 }
 
-/// Reorder the polygon vertices to start after the shared edge.
-fn reorder_polygon(coords: &[Coord<f64>], shared_edge_idx: usize) -> Vec<Coord<f64>> {
-    let len = coords.len();
+/// Reorder a face's vertex ring to start right after the shared edge.
+fn reorder_face(face: &[usize], shared_edge_idx: usize) -> Vec<usize> {
+    let len = face.len();
 
     // Handle wrap-around to avoid out-of-bounds access
     let after_shared_edge = if shared_edge_idx + 1 < len {
-        &coords[shared_edge_idx + 1..]
+        &face[shared_edge_idx + 1..]
     } else {
-        &coords[..0] // Empty slice when at the end
+        &face[..0] // Empty slice when at the end
     };
 
     after_shared_edge
         .iter()
-        .chain(coords[..=shared_edge_idx].iter())
+        .chain(face[..=shared_edge_idx].iter())
         .cloned()
         .collect()
 }
 
-
-/// Check if a polygon is convex by ensuring all vertices are convex.
-fn is_polygon_convex(polygon: &Polygon<f64>) -> bool {
-    let coords = polygon.exterior().coords_iter().collect::<Vec<_>>();
-    let len = coords.len();
-
-    // Print out the shape being checked for convexity
-    println!("Checking polygon for convexity: {:?}", coords);
+/// Check if a face (vertex-index ring) is convex by ensuring all its
+/// vertices are convex.
+fn is_face_convex<T: Coordinate>(vertices: &[Coord<T>], face: &[usize]) -> bool {
+    let len = face.len();
 
     for i in 0..len {
-        let prev = coords[(i + len - 1) % len];
-        let curr = coords[i];
-        let next = coords[(i + 1) % len];
+        let prev = vertices[face[(i + len - 1) % len]];
+        let curr = vertices[face[i]];
+        let next = vertices[face[(i + 1) % len]];
         if !is_convex(prev, curr, next) {
             println!(
-                "Polygon is not convex: vertex ({:?}, {:?}, {:?}) forms a concave angle.",
+                "Face is not convex: vertex ({:?}, {:?}, {:?}) forms a concave angle.",
                 prev, curr, next
             );
             return false;
         }
     }
 
-    println!("Polygon is convex.");
     true
 }
 
+/// Build a closed polygon from a vertex-index ring into the shared buffer.
+fn face_to_polygon<T: Coordinate>(vertices: &[Coord<T>], face: &[usize]) -> Polygon<T> {
+    let mut coords: Vec<Coord<T>> = face.iter().map(|&i| vertices[i]).collect();
+    coords.push(coords[0]);
+    Polygon::new(LineString::from(coords), vec![])
+}
 
-/// Triangulate a simple polygon (no holes) using the ear-clipping algorithm.
-fn triangulate(polygon: &Polygon<f64>) -> Vec<Polygon<f64>> {
-    let mut coords = polygon.exterior().coords_iter().collect::<Vec<_>>();
+/// Flatten a polygon's exterior and interior (hole) rings into a single
+/// simple ring by bridging each hole to the exterior, so the ear-clipping
+/// loop in `triangulate` never has to know holes exist.
+fn build_simple_ring<T: Coordinate>(polygon: &Polygon<T>) -> Result<Vec<Coord<T>>, DecompositionError> {
+    let mut outer = polygon.exterior().coords_iter().collect::<Vec<_>>();
+    if outer.first() == outer.last() {
+        outer.pop();
+    }
 
-    // Check if the polygon is valid for triangulation
-    if coords.len() < 4 {
-        panic!("Polygon must have at least 4 vertices for triangulation."); // Must be at least a triangle
+    let mut holes: Vec<Vec<Coord<T>>> = polygon
+        .interiors()
+        .iter()
+        .map(|ring| {
+            let mut coords = ring.coords_iter().collect::<Vec<_>>();
+            if coords.first() == coords.last() {
+                coords.pop();
+            }
+            coords
+        })
+        .filter(|coords| coords.len() >= 3)
+        .collect();
+
+    if !outer.iter().chain(holes.iter().flatten()).all(|c| is_finite_coord(*c)) {
+        return Err(DecompositionError::NonFiniteCoordinate);
+    }
+
+    // Bridge the rightmost hole first. Once a hole is spliced in, its ring and
+    // bridge edges are part of the outer ring, so every later hole's bridge
+    // candidates are checked for visibility against them as well.
+    holes.sort_by(|a, b| max_x(b).partial_cmp(&max_x(a)).unwrap_or(Ordering::Equal));
+
+    for hole in &holes {
+        outer = bridge_hole(&outer, hole)?;
     }
 
-    // Ensure the polygon is closed (last point == first point)
-    if coords.first() == coords.last() {
-        coords.pop();
+    Ok(outer)
+}
+
+/// The largest x-coordinate among a ring's vertices.
+fn max_x<T: Coordinate>(ring: &[Coord<T>]) -> T {
+    ring.iter().map(|c| c.x).fold(T::neg_infinity(), T::max)
+}
+
+/// Whether both of a coordinate's components are finite, i.e. neither NaN
+/// nor infinite. Checked once up front so the `partial_cmp` calls used to
+/// order and bridge holes by x-coordinate never see a NaN.
+fn is_finite_coord<T: Coordinate>(c: Coord<T>) -> bool {
+    c.x.is_finite() && c.y.is_finite()
+}
+
+/// Splice a hole into an outer ring by bridging its rightmost vertex to a
+/// mutually-visible vertex on the outer ring, duplicating both bridge
+/// endpoints so the result is a single simple (if self-touching) ring.
+fn bridge_hole<T: Coordinate>(outer: &[Coord<T>], hole: &[Coord<T>]) -> Result<Vec<Coord<T>>, DecompositionError> {
+    let hole_len = hole.len();
+    let hole_start = (0..hole_len)
+        .max_by(|&a, &b| hole[a].x.partial_cmp(&hole[b].x).unwrap_or(Ordering::Equal))
+        .expect("hole ring must not be empty");
+    let hole_point = hole[hole_start];
+
+    let bridge_idx = find_bridge_vertex(outer, hole, hole_start, hole_point)?;
+
+    let mut spliced = Vec::with_capacity(outer.len() + hole_len + 2);
+    spliced.extend_from_slice(&outer[..=bridge_idx]);
+
+    // Walk the hole backwards from its rightmost vertex so it's wound
+    // opposite to the outer ring, keeping the bridged shape's interior
+    // consistent with plain ear-clipping winding rules.
+    for k in 0..hole_len {
+        let idx = (hole_start + hole_len - k) % hole_len;
+        spliced.push(hole[idx]);
     }
+    spliced.push(hole[hole_start]);
+    spliced.push(outer[bridge_idx]);
+
+    spliced.extend_from_slice(&outer[bridge_idx + 1..]);
+    Ok(spliced)
+}
 
+/// Find a vertex on the outer ring that `hole_point` (the hole's vertex at
+/// `hole_start`) can bridge to without the bridge segment crossing the rest
+/// of either ring. A rightward ray cast from the hole point only finds *a*
+/// nearby candidate; on a non-convex outer ring or a non-convex hole, the
+/// straight line to that candidate can still cut across a reflex notch
+/// elsewhere in either boundary, so every candidate is checked for an
+/// unobstructed line of sight against every other edge of both rings, and
+/// the nearest visible one wins.
+fn find_bridge_vertex<T: Coordinate>(
+    outer: &[Coord<T>],
+    hole: &[Coord<T>],
+    hole_start: usize,
+    hole_point: Coord<T>,
+) -> Result<usize, DecompositionError> {
+    let len = outer.len();
+    let mut best: Option<(usize, T)> = None;
+
+    for i in 0..len {
+        if !is_visible(outer[i], hole_point, outer, Some(i), hole, Some(hole_start)) {
+            continue;
+        }
+
+        let candidate = outer[i];
+        let dx = candidate.x - hole_point.x;
+        let dy = candidate.y - hole_point.y;
+        let dist_sq = dx * dx + dy * dy;
+
+        if best.is_none_or(|(_, best_dist)| dist_sq < best_dist) {
+            best = Some((i, dist_sq));
+        }
+    }
+
+    best.map(|(idx, _)| idx).ok_or(DecompositionError::NoVisibleBridge)
+}
+
+/// Whether the straight segment from `from` to `to` stays clear of every
+/// other edge of both rings, i.e. the two points are mutually visible
+/// across the combined boundary. `skip_a`/`skip_b` name the `ring_a`
+/// vertex that `from` (if any) and the `ring_b` vertex that `to` (if any)
+/// coincide with, so the edges incident to them aren't mistaken for
+/// crossings at their own shared endpoint.
+fn is_visible<T: Coordinate>(
+    from: Coord<T>,
+    to: Coord<T>,
+    ring_a: &[Coord<T>],
+    skip_a: Option<usize>,
+    ring_b: &[Coord<T>],
+    skip_b: Option<usize>,
+) -> bool {
+    let blocked_by = |ring: &[Coord<T>], skip: Option<usize>| {
+        let len = ring.len();
+        (0..len).any(|i| {
+            let j = (i + 1) % len;
+            if Some(i) == skip || Some(j) == skip {
+                return false;
+            }
+            segments_properly_intersect(from, to, ring[i], ring[j])
+        })
+    };
+
+    !blocked_by(ring_a, skip_a) && !blocked_by(ring_b, skip_b)
+}
+
+/// Whether two segments cross at an interior point of both, using the
+/// standard opposite-orientation test on each pair of endpoints. Segments
+/// that merely touch at a shared endpoint don't count as crossing.
+fn segments_properly_intersect<T: Coordinate>(p1: Coord<T>, p2: Coord<T>, p3: Coord<T>, p4: Coord<T>) -> bool {
+    let d1 = cross_product(p3, p4, p1);
+    let d2 = cross_product(p3, p4, p2);
+    let d3 = cross_product(p1, p2, p3);
+    let d4 = cross_product(p1, p2, p4);
+
+    ((d1 > T::zero() && d2 < T::zero()) || (d1 < T::zero() && d2 > T::zero()))
+        && ((d3 > T::zero() && d4 < T::zero()) || (d3 < T::zero() && d4 > T::zero()))
+}
+
+/// Triangulate a polygon, bridging any holes into the exterior ring first,
+/// then running the ear-clipping algorithm. Returns the shared vertex buffer
+/// alongside triangles as index triples into it, so downstream adjacency and
+/// merging never have to compare coordinates.
+///
+/// A vertex whose ear has zero area (it is collinear with its neighbours) is
+/// removed from the working ring without emitting a triangle, so no sliver
+/// triangle with no area ever reaches the output.
+fn triangulate<T: Coordinate>(polygon: &Polygon<T>) -> Result<(Vec<Coord<T>>, Vec<Triangle>), DecompositionError> {
+    let coords = build_simple_ring(polygon)?;
+
+    if coords.len() < 3 {
+        return Err(DecompositionError::TooFewVertices { got: coords.len() });
+    }
+
+    let vertices = coords;
+
+    // Working ring of live vertex indices, shrunk as ears are clipped.
+    let mut ring: Vec<usize> = (0..vertices.len()).collect();
     let mut triangles = Vec::new();
 
     // Iteratively find and clip ears
-    while coords.len() > 3 {
+    while ring.len() > 3 {
         let mut ear_found = false;
 
-        for i in 0..coords.len() {
-            let prev_idx = (i + coords.len() - 1) % coords.len();
-            let next_idx = (i + 1) % coords.len();
+        for i in 0..ring.len() {
+            let prev_idx = (i + ring.len() - 1) % ring.len();
+            let next_idx = (i + 1) % ring.len();
+
+            let p_prev = vertices[ring[prev_idx]];
+            let p_curr = vertices[ring[i]];
+            let p_next = vertices[ring[next_idx]];
+
+            let area = cross_product(p_prev, p_curr, p_next);
 
-            let p_prev = coords[prev_idx];
-            let p_curr = coords[i];
-            let p_next = coords[next_idx];
+            // A collinear spike contributes no area; drop it without ever
+            // emitting a degenerate triangle for it.
+            if is_nearly_zero(area) {
+                ring.remove(i);
+                ear_found = true;
+                break;
+            }
 
             // Check if this is a convex vertex
-            if is_convex(p_prev, p_curr, p_next) {
+            if area >= T::zero() {
                 // Check if the triangle formed by these points is an "ear"
-                if is_ear(&coords, prev_idx, i, next_idx) {
-                    // Create a new triangle
-                    let ear = vec![p_prev, p_curr, p_next, p_prev];
-                    triangles.push(Polygon::new(LineString::from(ear), vec![]));
+                if is_ear(&vertices, &ring, prev_idx, i, next_idx) {
+                    triangles.push([ring[prev_idx], ring[i], ring[next_idx]]);
 
-                    // Remove the ear vertex from the polygon
-                    coords.remove(i);
+                    // Remove the ear vertex from the working ring
+                    ring.remove(i);
 
                     ear_found = true;
                     break;
@@ -244,37 +570,52 @@ fn triangulate(polygon: &Polygon<f64>) -> Vec<Polygon<f64>> {
         }
 
         if !ear_found {
-            panic!("No ears found; the polygon might be invalid or self-intersecting.");
+            return Err(DecompositionError::NoEarFound);
         }
     }
 
     // Add the final remaining triangle
-    let final_triangle = vec![coords[0], coords[1], coords[2], coords[0]];
-    triangles.push(Polygon::new(LineString::from(final_triangle), vec![]));
+    triangles.push([ring[0], ring[1], ring[2]]);
 
-    triangles
+    Ok((vertices, triangles))
 }
 
-fn is_convex(p1: Coord<f64>, p2: Coord<f64>, p3: Coord<f64>) -> bool {
-    cross_product(p1, p2, p3) >= 0.0
+fn is_convex<T: Coordinate>(p1: Coord<T>, p2: Coord<T>, p3: Coord<T>) -> bool {
+    cross_product(p1, p2, p3) >= T::zero()
 }
 
 /// Compute the cross product of three points.
-fn cross_product(p1: Coord<f64>, p2: Coord<f64>, p3: Coord<f64>) -> f64 {
+fn cross_product<T: Coordinate>(p1: Coord<T>, p2: Coord<T>, p3: Coord<T>) -> T {
     (p2.x - p1.x) * (p3.y - p2.y) - (p2.y - p1.y) * (p3.x - p2.x)
 }
 
+/// Whether a cross product is close enough to zero to treat as collinear,
+/// rather than relying on exact float equality.
+fn is_nearly_zero<T: Coordinate>(value: T) -> bool {
+    value.abs() <= T::epsilon() * T::from(1_000).unwrap()
+}
+
 /// Check if a triangle is an "ear" (no other points are inside the triangle).
-fn is_ear(coords: &[Coord<f64>], prev_idx: usize, curr_idx: usize, next_idx: usize) -> bool {
-    let p1 = coords[prev_idx];
-    let p2 = coords[curr_idx];
-    let p3 = coords[next_idx];
+fn is_ear<T: Coordinate>(vertices: &[Coord<T>], ring: &[usize], prev_idx: usize, curr_idx: usize, next_idx: usize) -> bool {
+    let p1 = vertices[ring[prev_idx]];
+    let p2 = vertices[ring[curr_idx]];
+    let p3 = vertices[ring[next_idx]];
 
-    for (i, &point) in coords.iter().enumerate() {
+    for (i, &vertex_idx) in ring.iter().enumerate() {
         if i == prev_idx || i == curr_idx || i == next_idx {
             continue; // Skip vertices of the current triangle
         }
 
+        let point = vertices[vertex_idx];
+        // A hole bridge duplicates its two endpoint vertices elsewhere in the
+        // ring; those duplicates coincide exactly with one of this ear's own
+        // corners and must not block it. (This also means two *unrelated*
+        // ring vertices that happen to share a coordinate, e.g. a
+        // self-touching polygon, are not treated as blocking either.)
+        if point == p1 || point == p2 || point == p3 {
+            continue;
+        }
+
         if point_in_triangle(point, p1, p2, p3) {
             return false; // If any point is inside the triangle, it is not an ear
         }
@@ -284,15 +625,192 @@ fn is_ear(coords: &[Coord<f64>], prev_idx: usize, curr_idx: usize, next_idx: usi
 }
 
 /// Check if a point is inside a triangle using barycentric coordinates.
-fn point_in_triangle(pt: Coord<f64>, v1: Coord<f64>, v2: Coord<f64>, v3: Coord<f64>) -> bool {
+fn point_in_triangle<T: Coordinate>(pt: Coord<T>, v1: Coord<T>, v2: Coord<T>, v3: Coord<T>) -> bool {
     let d1 = cross_product(v1, v2, pt);
     let d2 = cross_product(v2, v3, pt);
     let d3 = cross_product(v3, v1, pt);
 
-    // The point is inside if all cross products have the same sign
-    (d1 >= 0.0 && d2 >= 0.0 && d3 >= 0.0) || (d1 <= 0.0 && d2 <= 0.0 && d3 <= 0.0)
+    (d1 >= T::zero() && d2 >= T::zero() && d3 >= T::zero())
+        || (d1 <= T::zero() && d2 <= T::zero() && d3 <= T::zero())
+}
+
+// ---------------------------------------------------------------------------
+// 3D: convex decomposition of a triangle-mesh polyhedron by face merging.
+//
+// This mirrors the 2D Hertel-Mehlhorn idea above (merge edge-adjacent faces
+// while the result stays convex) but one dimension up: faces are merged only
+// when they're (near-)coplanar *and* the merged boundary is convex in that
+// plane. `merge_faces`/`find_shared_edges`/`edge_key` are reused as-is since
+// they only ever operated on vertex indices, never on 2D coordinates.
+// ---------------------------------------------------------------------------
+
+/// A point (or free vector) in 3D space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Vec3 {
+    x: f64,
+    y: f64,
+    z: f64,
 }
 
+impl Vec3 {
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3 { x: self.x - other.x, y: self.y - other.y, z: self.z - other.z }
+    }
+
+    fn cross(self, other: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    fn dot(self, other: Vec3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    fn scale(self, s: f64) -> Vec3 {
+        Vec3 { x: self.x * s, y: self.y * s, z: self.z * s }
+    }
+}
+
+/// A face's supporting plane: unit normal plus signed distance from the origin.
+#[derive(Debug, Clone, Copy)]
+struct Plane {
+    normal: Vec3,
+    offset: f64,
+}
+
+impl Plane {
+    fn from_triangle(vertices: &[Vec3], triangle: &Face3) -> Result<Plane, DecompositionError> {
+        let a = vertices[triangle[0]];
+        let b = vertices[triangle[1]];
+        let c = vertices[triangle[2]];
+        let normal = b.sub(a).cross(c.sub(a));
+        let length = normal.length();
+        if length == 0.0 {
+            return Err(DecompositionError::DegenerateFace);
+        }
+        let normal = normal.scale(1.0 / length);
+        Ok(Plane { normal, offset: normal.dot(a) })
+    }
+
+    fn signed_distance(&self, point: Vec3) -> f64 {
+        self.normal.dot(point) - self.offset
+    }
+}
+
+/// A triangular face of a 3D mesh, as indices into a shared vertex buffer.
+type Face3 = [usize; 3];
+
+/// A convex group of merged mesh faces, keeping the supporting plane they
+/// were all tested against so a collision shape can be built straight from
+/// the boundary ring.
+struct ConvexFaceGroup {
+    boundary: Vec<usize>,
+    plane: Plane,
+}
+
+/// How far (in the same units as the vertex buffer) a merged face's vertices
+/// may stray from their shared plane and still be considered coplanar.
+const PLANAR_TOLERANCE: f64 = 1e-6;
+
+/// Merge coplanar, convex-adjacent triangular faces of a mesh into larger
+/// convex polygons, analogous to 2D Hertel-Mehlhorn but tested against each
+/// candidate merge's supporting plane instead of a single shared 2D plane.
+fn merge_convex_faces(vertices: &[Vec3], triangles: &[Face3]) -> Result<Vec<ConvexFaceGroup>, DecompositionError> {
+    let mut edge_faces = find_shared_edges(triangles);
+    let diagonals: Vec<EdgeKey> = edge_faces
+        .iter()
+        .filter(|(_, incident)| incident.len() == 2)
+        .map(|(&edge, _)| edge)
+        .collect();
+
+    let mut faces: Vec<Vec<usize>> = triangles.iter().map(|t| t.to_vec()).collect();
+    let mut planes: Vec<Plane> = triangles
+        .iter()
+        .map(|t| Plane::from_triangle(vertices, t))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut alive = vec![true; faces.len()];
+    let mut removed = vec![false; diagonals.len()];
+    let mut deleted_edges: HashSet<EdgeKey> = HashSet::new();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for (d, &edge) in diagonals.iter().enumerate() {
+            if removed[d] {
+                continue;
+            }
+
+            let incident = &edge_faces[&edge];
+            if incident.len() != 2 {
+                continue;
+            }
+            let (f1, f2) = (incident[0], incident[1]);
+
+            let merged_face = merge_faces(&faces[f1], &faces[f2], edge)?;
+            let plane = planes[f1];
+
+            let coplanar = merged_face
+                .iter()
+                .all(|&vi| plane.signed_distance(vertices[vi]).abs() <= PLANAR_TOLERANCE);
+
+            if coplanar && is_3d_face_convex(vertices, &merged_face, &plane) {
+                let new_face = faces.len();
+                update_edge_faces(&mut edge_faces, &faces[f1], f1, new_face);
+                update_edge_faces(&mut edge_faces, &faces[f2], f2, new_face);
+                edge_faces.remove(&edge);
+                deleted_edges.insert(edge);
+
+                faces.push(merged_face);
+                planes.push(plane);
+                alive.push(true);
+                alive[f1] = false;
+                alive[f2] = false;
+                removed[d] = true;
+                changed = true;
+            }
+        }
+    }
+
+    println!("Merged away {} internal edges across {} faces.", deleted_edges.len(), faces.len());
+
+    Ok(faces
+        .into_iter()
+        .zip(planes)
+        .enumerate()
+        .filter(|(i, _)| alive[*i])
+        .map(|(_, (boundary, plane))| ConvexFaceGroup { boundary, plane })
+        .collect())
+}
+
+/// Check that a merged face's boundary is convex within its supporting
+/// plane: for every boundary edge, every vertex of the face must lie on the
+/// non-negative side of the half-plane that edge cuts out (the "positive
+/// side" test), using the plane normal to tell which side is inward.
+fn is_3d_face_convex(vertices: &[Vec3], face: &[usize], plane: &Plane) -> bool {
+    let len = face.len();
+
+    for i in 0..len {
+        let a = vertices[face[i]];
+        let b = vertices[face[(i + 1) % len]];
+        let inward = plane.normal.cross(b.sub(a));
+
+        for &vi in face {
+            if inward.dot(vertices[vi].sub(a)) < -PLANAR_TOLERANCE {
+                return false;
+            }
+        }
+    }
+
+    true
+}
 
 fn main() {
     // Define a simple polygon
@@ -327,18 +845,71 @@ fn main() {
     let polygon = Polygon::new(LineString::from(coords), vec![]);
 
     // Perform the Hertel-Mehlhorn convex decomposition
-    let convex_polygons = hertel_mehlhorn(&polygon);
+    match hertel_mehlhorn(&polygon) {
+        Ok(convex_polygons) => {
+            for (i, convex_polygon) in convex_polygons.iter().enumerate() {
+                println!("Convex Polygon {}: {:?}", i + 1, convex_polygon);
+            }
+        }
+        Err(err) => eprintln!("Failed to decompose polygon: {err}"),
+    }
+
+    // Decompose the same polygon into a navmesh: convex cells plus the
+    // portal graph connecting them, ready for A*/Dijkstra pathfinding.
+    match decompose_to_navmesh(&polygon) {
+        Ok(navmesh) => {
+            for (i, (cell, centroid)) in navmesh.polygons.iter().zip(&navmesh.centroids).enumerate() {
+                // Sanity-check that every emitted cell is actually convex,
+                // using its own exterior ring as a freshly-indexed face.
+                let mut cell_vertices: Vec<Coord<f64>> = cell.exterior().coords_iter().collect();
+                if cell_vertices.first() == cell_vertices.last() {
+                    cell_vertices.pop();
+                }
+                let cell_face: Vec<usize> = (0..cell_vertices.len()).collect();
+                println!(
+                    "Navmesh cell {}: {:?} (centroid {:?}, convex: {})",
+                    i + 1,
+                    cell,
+                    centroid,
+                    is_face_convex(&cell_vertices, &cell_face)
+                );
+            }
+            for portal in &navmesh.portals {
+                println!(
+                    "Portal between cells {} and {}: {:?} -> {:?}",
+                    portal.cells.0, portal.cells.1, portal.endpoints.0, portal.endpoints.1
+                );
+            }
+        }
+        Err(err) => eprintln!("Failed to build navmesh: {err}"),
+    }
 
-    // Print the resulting convex polygons
-    for (i, convex_polygon) in convex_polygons.iter().enumerate() {
-        println!("Convex Polygon {}: {:?}", i + 1, convex_polygon);
+    // Merge a small triangle mesh's coplanar faces into convex 3D groups.
+    let mesh_vertices = vec![
+        Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+        Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+        Vec3 { x: 1.0, y: 1.0, z: 0.0 },
+        Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+    ];
+    let mesh_triangles: Vec<Face3> = vec![[0, 1, 2], [0, 2, 3]];
+    match merge_convex_faces(&mesh_vertices, &mesh_triangles) {
+        Ok(groups) => {
+            for (i, group) in groups.iter().enumerate() {
+                println!(
+                    "Convex face group {}: boundary {:?}, plane normal {:?}",
+                    i + 1,
+                    group.boundary,
+                    group.plane.normal
+                );
+            }
+        }
+        Err(err) => eprintln!("Failed to merge mesh faces: {err}"),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use geo::{polygon, LineString};
 
     #[test]
     fn test_simple_square_polygon() {
@@ -350,7 +921,7 @@ mod tests {
             (0.0, 0.0),
         ];
         let polygon = Polygon::new(LineString::from(coords), vec![]);
-        let result = hertel_mehlhorn(&polygon);
+        let result = hertel_mehlhorn(&polygon).unwrap();
 
         assert_eq!(result.len(), 1); // A square is already convex
     }
@@ -366,7 +937,7 @@ mod tests {
             (0.0, 0.0),
         ];
         let polygon = Polygon::new(LineString::from(coords), vec![]);
-        let result = hertel_mehlhorn(&polygon);
+        let result = hertel_mehlhorn(&polygon).unwrap();
 
         assert!(result.len() > 1); // Should split into multiple convex polygons
     }
@@ -380,20 +951,19 @@ mod tests {
             (0.0, 0.0),
         ];
         let polygon = Polygon::new(LineString::from(coords), vec![]);
-        let result = hertel_mehlhorn(&polygon);
+        let result = hertel_mehlhorn(&polygon).unwrap();
 
         assert_eq!(result.len(), 1); // A triangle is already convex
     }
 
     #[test]
     fn test_shared_edge_detection() {
-        let triangles = vec![
-            polygon![(x: 0.0, y: 0.0), (x: 1.0, y: 1.0), (x: 2.0, y: 0.0)],
-            polygon![(x: 1.0, y: 1.0), (x: 2.0, y: 0.0), (x: 2.0, y: 2.0)],
-        ];
+        // Two triangles sharing the edge between vertex 1 and vertex 2.
+        let triangles: Vec<Triangle> = vec![[0, 1, 2], [1, 2, 3]];
         let shared_edges = find_shared_edges(&triangles);
 
-        assert_eq!(shared_edges.len(), 1); // One shared edge exists
+        let internal: Vec<_> = shared_edges.values().filter(|faces| faces.len() == 2).collect();
+        assert_eq!(internal.len(), 1); // One shared edge exists
     }
 
     #[test]
@@ -403,10 +973,9 @@ mod tests {
             (4.0, 0.0),
             (4.0, 4.0),
             (0.0, 4.0),
-            (0.0, 0.0),
         ];
-        let polygon_convex = Polygon::new(LineString::from(coords_convex), vec![]);
-        assert!(is_polygon_convex(&polygon_convex));
+        let vertices: Vec<Coord<f64>> = coords_convex.into_iter().map(Coord::from).collect();
+        assert!(is_face_convex(&vertices, &[0, 1, 2, 3]));
 
         let coords_concave = vec![
             (0.0, 0.0),
@@ -414,9 +983,188 @@ mod tests {
             (4.0, 4.0),
             (2.0, 2.0), // Concave point
             (0.0, 4.0),
+        ];
+        let vertices: Vec<Coord<f64>> = coords_concave.into_iter().map(Coord::from).collect();
+        assert!(!is_face_convex(&vertices, &[0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_triangulate_polygon_with_hole() {
+        let exterior = LineString::from(vec![
+            (0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0),
+        ]);
+        let hole = LineString::from(vec![
+            (3.0, 3.0), (7.0, 3.0), (7.0, 7.0), (3.0, 7.0), (3.0, 3.0),
+        ]);
+        let polygon = Polygon::new(exterior, vec![hole]);
+        let (vertices, triangles) = triangulate(&polygon).unwrap();
+
+        // Bridging duplicates the two bridge endpoints, so the simple ring
+        // has exterior_len + hole_len + 2 vertices, and ear-clipping yields
+        // ring_len - 2 triangles when no collinear vertex has to be dropped.
+        assert_eq!(vertices.len(), 4 + 4 + 2);
+        assert_eq!(triangles.len(), vertices.len() - 2);
+    }
+
+    #[test]
+    fn test_triangulate_non_convex_exterior_with_hole() {
+        // The reflex vertex at (5.0, 4.6) pulls the top edge inward, so a
+        // naive rightward bridge from the hole can land on a ring vertex
+        // whose line of sight is blocked by that notch.
+        let exterior = LineString::from(vec![
+            (0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (5.0, 4.6), (0.0, 10.0), (0.0, 0.0),
+        ]);
+        let hole = LineString::from(vec![
+            (2.0, 4.0), (2.3, 4.0), (2.3, 4.3), (2.0, 4.3), (2.0, 4.0),
+        ]);
+        let polygon = Polygon::new(exterior, vec![hole]);
+        let (vertices, triangles) = triangulate(&polygon).unwrap();
+
+        assert_eq!(vertices.len(), 5 + 4 + 2);
+        assert_eq!(triangles.len(), vertices.len() - 2);
+    }
+
+    #[test]
+    fn test_triangulate_concave_hole_bridge_stays_outside_hole() {
+        // The outer ring's vertex at (3.0, 9.0) is the nearest one to the
+        // hole's rightmost vertex and isn't blocked by any outer edge, but a
+        // straight bridge to it cuts through the hole's own reflex notch at
+        // (11.0, 10.0). The bridge must skip it and fall back to a vertex
+        // that's actually visible from outside the hole as well.
+        let exterior = LineString::from(vec![
+            (0.0, 0.0), (20.0, 0.0), (20.0, 20.0), (3.0, 9.0), (0.0, 20.0), (0.0, 0.0),
+        ]);
+        let hole = LineString::from(vec![
+            (9.0, 8.0), (13.0, 8.0), (13.0, 12.0), (9.0, 12.0), (11.0, 10.0), (9.0, 8.0),
+        ]);
+        let polygon = Polygon::new(exterior, vec![hole]);
+        let (vertices, triangles) = triangulate(&polygon).unwrap();
+
+        assert_eq!(vertices.len(), 5 + 5 + 2);
+        assert_eq!(triangles.len(), vertices.len() - 2);
+    }
+
+    #[test]
+    fn test_navmesh_portal_between_split_cells() {
+        let coords = vec![
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 4.0),
+            (2.0, 2.0), // Concave point
+            (0.0, 4.0),
+            (0.0, 0.0),
+        ];
+        let polygon = Polygon::new(LineString::from(coords), vec![]);
+        let navmesh = decompose_to_navmesh(&polygon).unwrap();
+
+        assert_eq!(navmesh.polygons.len(), navmesh.centroids.len());
+        assert!(navmesh.polygons.len() > 1);
+        assert!(!navmesh.portals.is_empty());
+        for portal in &navmesh.portals {
+            assert!(portal.cells.0 < navmesh.polygons.len());
+            assert!(portal.cells.1 < navmesh.polygons.len());
+            assert_ne!(portal.cells.0, portal.cells.1);
+            assert_ne!(portal.endpoints.0, portal.endpoints.1);
+        }
+    }
+
+    #[test]
+    fn test_f32_decomposition() {
+        let coords: Vec<(f32, f32)> = vec![
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 4.0),
+            (0.0, 4.0),
+            (0.0, 0.0),
+        ];
+        let polygon = Polygon::new(LineString::from(coords), vec![]);
+        let result = hertel_mehlhorn(&polygon).unwrap();
+
+        assert_eq!(result.len(), 1);
+    }
+
+    #[test]
+    fn test_collinear_spike_does_not_panic() {
+        // The point (2.0, 0.0) is collinear with its neighbors, previously a
+        // zero-area "ear" the old `>= 0.0` convexity check would clip.
+        let coords = vec![
             (0.0, 0.0),
+            (2.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 4.0),
+            (0.0, 4.0),
+            (0.0, 0.0),
+        ];
+        let polygon = Polygon::new(LineString::from(coords), vec![]);
+        assert!(hertel_mehlhorn(&polygon).is_ok());
+    }
+
+    #[test]
+    fn test_too_few_vertices_reports_error() {
+        let polygon = Polygon::new(LineString::from(vec![(0.0, 0.0), (1.0, 0.0)]), vec![]);
+        assert_eq!(
+            triangulate(&polygon).unwrap_err(),
+            DecompositionError::TooFewVertices { got: 2 }
+        );
+    }
+
+    #[test]
+    fn test_nan_hole_coordinate_reports_error_instead_of_panicking() {
+        let coords = vec![
+            (0.0, 0.0),
+            (4.0, 0.0),
+            (4.0, 4.0),
+            (0.0, 4.0),
+            (0.0, 0.0),
+        ];
+        let hole = vec![
+            (1.0, 1.0),
+            (f64::NAN, 2.0),
+            (2.0, 2.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+        ];
+        let polygon = Polygon::new(LineString::from(coords), vec![LineString::from(hole)]);
+
+        assert_eq!(
+            triangulate(&polygon).unwrap_err(),
+            DecompositionError::NonFiniteCoordinate
+        );
+    }
+
+    #[test]
+    fn test_merge_coplanar_quad_into_one_face() {
+        // A unit square in the z = 0 plane, split into two triangles along
+        // one diagonal.
+        let vertices = vec![
+            Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+            Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+            Vec3 { x: 1.0, y: 1.0, z: 0.0 },
+            Vec3 { x: 0.0, y: 1.0, z: 0.0 },
         ];
-        let polygon_concave = Polygon::new(LineString::from(coords_concave), vec![]);
-        assert!(!is_polygon_convex(&polygon_concave));
+        let triangles: Vec<Face3> = vec![[0, 1, 2], [0, 2, 3]];
+
+        let groups = merge_convex_faces(&vertices, &triangles).unwrap();
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].boundary.len(), 4);
+        assert!((groups[0].plane.normal.z.abs() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_non_coplanar_faces_stay_separate() {
+        // Two triangles sharing an edge but folded like an open book, not
+        // lying in a common plane.
+        let vertices = vec![
+            Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+            Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+            Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+            Vec3 { x: 0.0, y: 1.0, z: 1.0 },
+        ];
+        let triangles: Vec<Face3> = vec![[0, 1, 2], [1, 3, 2]];
+
+        let groups = merge_convex_faces(&vertices, &triangles).unwrap();
+
+        assert_eq!(groups.len(), 2);
     }
 }